@@ -0,0 +1,87 @@
+use ethers::utils::rlp;
+
+/// Decoded Ethereum account state, i.e. the four fields RLP-encoded as the terminal value of a
+/// state trie proof (see EIP-1186).
+pub struct AccountState
+{
+    /// Number of transactions sent from this account, or number of contract creations if this
+    /// account is a contract
+    pub nonce: u64,
+    /// Big endian encoding of the account's balance, in wei
+    pub balance: Vec<u8>,
+    /// Root hash of this account's storage trie
+    pub storage_hash: Vec<u8>,
+    /// Hash of this account's EVM code
+    pub code_hash: Vec<u8>,
+}
+
+impl AccountState
+{
+    /// Account state Toml string formatter. Returns a string with the table entries corresponding
+    /// to an `AccountState`.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the TOML table the account state is emitted under
+    pub fn to_toml_string(&self, name: &str) -> String
+    {
+        format!(
+            "[{}]\nnonce = {:#04x?}\nbalance = {:#04x?}\nstorage_hash = {:#04x?}\ncode_hash = {:#04x?}",
+            name, self.nonce, self.balance, self.storage_hash, self.code_hash
+        )
+    }
+}
+
+/// Decodes the RLP-encoded account state found as the terminal value of a state trie proof.
+/// Returns the four decomposed fields so that e.g. the storage root or code hash can be
+/// constrained directly in a Noir circuit without re-parsing the packed value there.
+///
+/// # Arguments
+/// * `value` - RLP-encoded account state, i.e. the state trie proof's terminal value
+pub fn decode_account_state(value: &[u8]) -> Result<AccountState, Box<dyn std::error::Error>>
+{
+    let rlp = rlp::Rlp::new(value);
+    if rlp.item_count()? != 4
+    {
+        return Err("Account state must be an RLP list of four fields".into());
+    }
+
+    Ok(AccountState {
+        nonce: rlp.at(0)?.as_val()?,
+        balance: rlp.at(1)?.as_val()?,
+        storage_hash: rlp.at(2)?.as_val()?,
+        code_hash: rlp.at(3)?.as_val()?,
+    })
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn decodes_a_well_formed_account_state()
+    {
+        let mut stream = rlp::RlpStream::new_list(4);
+        stream.append(&7u64);
+        stream.append(&vec![0x01, 0x02]);
+        stream.append(&vec![0xaa; 32]);
+        stream.append(&vec![0xbb; 32]);
+
+        let account_state = decode_account_state(&stream.out()).unwrap();
+        assert_eq!(account_state.nonce, 7);
+        assert_eq!(account_state.balance, vec![0x01, 0x02]);
+        assert_eq!(account_state.storage_hash, vec![0xaa; 32]);
+        assert_eq!(account_state.code_hash, vec![0xbb; 32]);
+    }
+
+    #[test]
+    fn rejects_an_account_state_with_the_wrong_number_of_fields()
+    {
+        let mut stream = rlp::RlpStream::new_list(3);
+        stream.append(&1u64);
+        stream.append(&vec![0x01]);
+        stream.append(&vec![0x02]);
+
+        assert!(decode_account_state(&stream.out()).is_err());
+    }
+}
@@ -30,15 +30,16 @@ struct Cli
 #[derive(Subcommand)]
 enum Commands
 {
-    /// Fetch storage proof
+    /// Fetch storage proof(s)
     StorageProof
     {
-        /// Address of the account from which a storage proof is retrieved
+        /// Address of the account from which storage proofs are retrieved
         #[arg(short, long, value_name = "ADDRESS")]
         address: Address,
-        /// Key of the storage slot for which a storage proof is retrieved
-        #[arg(short, long, value_name = "KEY")]
-        key: H256,
+        /// Key of a storage slot for which a storage proof is retrieved. May be given multiple
+        /// times, or as a comma-separated list, to fetch several slots in a single call.
+        #[arg(short, long, value_name = "KEY", value_delimiter = ',', required = true)]
+        key: Vec<H256>,
     },
     /// Fetch state proof
     StateProof
@@ -47,6 +48,28 @@ enum Commands
         #[arg(short, long, value_name = "ADDRESS")]
         address: Address,
     },
+    /// Fetch a state proof together with a storage proof, linked via the account's storageHash
+    AccountStorageProof
+    {
+        /// Address of the account whose state and storage are proven
+        #[arg(short, long, value_name = "ADDRESS")]
+        address: Address,
+        /// Key of the storage slot for which a storage proof is retrieved
+        #[arg(short, long, value_name = "KEY")]
+        key: H256,
+    },
+    /// Fetch a block header, verified against the block hash
+    BlockHeaderProof,
+    /// Build a trie from a local key/value set and produce a proof for one key, without an RPC node
+    LocalProof
+    {
+        /// Path to a JSON file containing an array of `{ "key": "0x..", "value": "0x.." }` entries
+        #[arg(long, value_name = "KV_FILE")]
+        kv_file: String,
+        /// Key to produce a trie proof for; must be one of the keys in `kv_file`
+        #[arg(short, long, value_name = "KEY")]
+        key: Bytes,
+    },
 }
 
 #[tokio::main]
@@ -54,8 +77,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>>
 {
     // Parse args
     let cli = Cli::parse();
-    // Max depth and RPC URL *must* be specified
+    // Max depth *must* be specified
     let max_depth = cli.max_depth.ok_or("--max-depth must be specified!")?;
+
+    // LocalProof needs no RPC node at all, so it is handled before a provider is set up; every
+    // other proof type is fetched over RPC and falls through to the `rest` arm below
+    let rest = match cli.proof_type {
+        Commands::LocalProof { kv_file, key } => {
+            let entries: Vec<KvEntry> = serde_json::from_str(&std::fs::read_to_string(kv_file)?)?;
+            let entries: Vec<(Vec<u8>, Vec<u8>)> = entries
+                .into_iter()
+                .map(|e| (e.key.to_vec(), e.value.to_vec()))
+                .collect();
+
+            let (root, proof) = build_local_proof(&entries, &key, max_depth)?;
+
+            println!("{} = {:#04x?}\n", cli.root_name.unwrap_or("root".to_string()), root);
+            println!("{}", proof.to_toml_string(&cli.proof_name.unwrap_or("local_proof".to_string())));
+
+            return Ok(());
+        }
+        rest => rest,
+    };
+
+    // Every other proof type is fetched over RPC, and so requires a provider
     let rpc_url = cli.rpc_url.ok_or("--rpc-url must be specified!")?;
 
     // Specify provider
@@ -67,22 +112,40 @@ async fn main() -> Result<(), Box<dyn std::error::Error>>
         None => provider.get_block_number().await?,
     };
 
-    // Cases for different proof types
-    match cli.proof_type {
+    // Cases for the remaining proof types
+    match rest {
         Commands::StorageProof { address, key } => {
-            let (storage_root, storage_proof) =
+            let (storage_root, storage_proofs) =
                 fetch_storage_proof(provider, block_number, key, address, max_depth).await?;
 
             println!("{} = {:#04x?}\n", cli.root_name.unwrap_or("storage_root".to_string()), storage_root);
-            println!("{}", storage_proof.to_toml_string(&cli.proof_name.unwrap_or("storage_proof".to_string())));
+            println!("{}", TrieProof::to_toml_array_string(&storage_proofs, &cli.proof_name.unwrap_or("storage_proof".to_string())));
         }
         Commands::StateProof { address } => {
-            let (state_root, state_proof) =
+            let (state_root, state_proof, account_state) =
                 fetch_state_proof(provider, block_number, address, max_depth).await?;
 
             println!("{} = {:#04x?}\n", cli.root_name.unwrap_or("state_root".to_string()), state_root);
-            println!("{}", state_proof.to_toml_string(&cli.proof_name.unwrap_or("state_proof".to_string())));
+            println!("{}\n", state_proof.to_toml_string(&cli.proof_name.unwrap_or("state_proof".to_string())));
+            println!("{}", account_state.to_toml_string("account_state"));
+        }
+        Commands::AccountStorageProof { address, key } => {
+            let (state_root, state_proof, account_state, storage_proof) =
+                fetch_account_storage_proof(provider, block_number, address, key, max_depth).await?;
+
+            println!("{} = {:#04x?}\n", cli.root_name.unwrap_or("state_root".to_string()), state_root);
+            println!("{}\n", state_proof.to_toml_string(&format!("{}_state", cli.proof_name.clone().unwrap_or("account".to_string()))));
+            println!("{}\n", account_state.to_toml_string("account_state"));
+            println!("{}", storage_proof.to_toml_string(&format!("{}_storage", cli.proof_name.unwrap_or("account".to_string()))));
+        }
+        Commands::BlockHeaderProof => {
+            let (block_hash, header_proof) =
+                fetch_block_header_proof(provider, block_number).await?;
+
+            println!("{} = {:#04x?}\n", cli.root_name.unwrap_or("block_hash".to_string()), block_hash);
+            println!("{}", header_proof.to_toml_string(&cli.proof_name.unwrap_or("header_proof".to_string())));
         }
+        Commands::LocalProof { .. } => unreachable!("handled above, before a provider was set up"),
     };
 
     Ok(())
@@ -0,0 +1,194 @@
+use ethers::prelude::*;
+use ethers::utils::keccak256;
+use ethers::utils::rlp;
+
+/// Maximum length of an RLP-encoded block header in bytes
+const MAX_HEADER_LENGTH: usize = 700;
+
+/// Index of the state root among the canonically ordered header fields
+const STATE_ROOT_FIELD_INDEX: usize = 3;
+
+/// Block header proof struct mirroring the equivalent Noir code
+pub struct BlockHeaderProof
+{
+    /// RLP-encoded block header, padded with trailing zeros up to `MAX_HEADER_LENGTH` bytes
+    header: Vec<u8>,
+    /// Byte offset of the state root field within the (unpadded) RLP-encoded header
+    state_root_offset: usize,
+}
+
+impl BlockHeaderProof
+{
+    /// Block header proof Toml string formatter. Returns a string with the table entries
+    /// corresponding to a `BlockHeaderProof`.
+    ///
+    /// # Arguments
+    /// * `proof_name` - Name of the TOML table the proof is emitted under
+    pub fn to_toml_string(&self, proof_name: &str) -> String
+    {
+        format!(
+            "[{}]\nheader = {:#04x?}\nstate_root_offset = {:#04x?}",
+            proof_name, self.header, self.state_root_offset
+        )
+    }
+}
+
+/// Canonically RLP-encodes the fields of a block header, in the order defined by the Ethereum
+/// yellow paper, extended with `baseFeePerGas` (EIP-1559), `withdrawalsRoot` (Shanghai) and
+/// `blobGasUsed`/`excessBlobGas`/`parentBeaconBlockRoot` (Cancun) where present. Returns one
+/// already RLP-encoded item per field, so that the byte offset of any field within the final
+/// header can be computed by summing the lengths of the items preceding it.
+///
+/// # Arguments
+/// * `block` - Block whose header fields are encoded
+fn encode_header_fields(block: &Block<H256>) -> Result<Vec<Vec<u8>>, Box<dyn std::error::Error>>
+{
+    let mut fields = vec![
+        rlp::encode(&block.parent_hash.as_bytes()).to_vec(),
+        rlp::encode(&block.uncles_hash.as_bytes()).to_vec(),
+        rlp::encode(
+            &block
+                .author
+                .ok_or("Block is missing its author field")?
+                .as_bytes(),
+        )
+        .to_vec(),
+        rlp::encode(&block.state_root.as_bytes()).to_vec(),
+        rlp::encode(&block.transactions_root.as_bytes()).to_vec(),
+        rlp::encode(&block.receipts_root.as_bytes()).to_vec(),
+        rlp::encode(
+            &block
+                .logs_bloom
+                .ok_or("Block is missing its logsBloom field")?
+                .as_bytes(),
+        )
+        .to_vec(),
+        rlp::encode(&block.difficulty).to_vec(),
+        rlp::encode(&U256::from(
+            block.number.ok_or("Block is missing its number field")?.as_u64(),
+        ))
+        .to_vec(),
+        rlp::encode(&block.gas_limit).to_vec(),
+        rlp::encode(&block.gas_used).to_vec(),
+        rlp::encode(&block.timestamp).to_vec(),
+        rlp::encode(&block.extra_data.to_vec()).to_vec(),
+        rlp::encode(
+            &block
+                .mix_hash
+                .ok_or("Block is missing its mixHash field")?
+                .as_bytes(),
+        )
+        .to_vec(),
+        rlp::encode(
+            &block
+                .nonce
+                .ok_or("Block is missing its nonce field")?
+                .as_bytes(),
+        )
+        .to_vec(),
+    ];
+
+    // EIP-1559 chains append baseFeePerGas
+    if let Some(base_fee) = block.base_fee_per_gas
+    {
+        fields.push(rlp::encode(&base_fee).to_vec());
+    }
+
+    // Shanghai chains append withdrawalsRoot
+    if let Some(withdrawals_root) = block.withdrawals_root
+    {
+        fields.push(rlp::encode(&withdrawals_root.as_bytes()).to_vec());
+    }
+
+    // Cancun chains append blobGasUsed, excessBlobGas, and parentBeaconBlockRoot
+    if let Some(blob_gas_used) = block.blob_gas_used
+    {
+        fields.push(rlp::encode(&U256::from(blob_gas_used.as_u64())).to_vec());
+    }
+    if let Some(excess_blob_gas) = block.excess_blob_gas
+    {
+        fields.push(rlp::encode(&U256::from(excess_blob_gas.as_u64())).to_vec());
+    }
+    if let Some(parent_beacon_block_root) = block.parent_beacon_block_root
+    {
+        fields.push(rlp::encode(&parent_beacon_block_root.as_bytes()).to_vec());
+    }
+
+    Ok(fields)
+}
+
+/// Right-pads a byte vector with trailing zeros up to `max_len`. Returns the padded vector.
+/// Unlike `left_pad`, padding is appended at the end so that byte offsets into the unpadded
+/// prefix remain valid.
+///
+/// # Arguments
+/// * `v` - Byte vector
+/// * `max_len` - Desired size of the padded vector
+fn right_pad(v: &[u8], max_len: usize) -> Result<Vec<u8>, Box<dyn std::error::Error>>
+{
+    if v.len() > max_len
+    {
+        Err("The header exceeds its maximum expected length.".into())
+    } else {
+        let mut padded = v.to_vec();
+        padded.append(&mut vec![0u8; max_len - v.len()]);
+        Ok(padded)
+    }
+}
+
+/// Block header fetcher and verifier. Fetches the full header for `block_number`, RLP-encodes it
+/// canonically, and asserts that `keccak256` of the encoding equals the block hash reported by
+/// the node, before emitting the (padded) header bytes together with the byte offset of the
+/// state root field. A Noir circuit can then recover the state root from within a header it has
+/// already verified against a trusted block hash, rather than trusting the root directly.
+///
+/// # Arguments
+/// * `provider` - Provider for interacting with Ethereum JSON RPC API
+/// * `block_number` - Block number whose header is fetched
+pub async fn fetch_block_header_proof<T: JsonRpcClient>(
+    provider: Provider<T>,
+    block_number: U64,
+) -> Result<(Vec<u8>, BlockHeaderProof), Box<dyn std::error::Error>>
+{
+    let block: Block<H256> = provider
+        .get_block(block_number)
+        .await?
+        .ok_or(format!("Could not fetch block number {}", block_number))?;
+    let block_hash = block
+        .hash
+        .ok_or("Block is missing its hash (pending block?)")?
+        .as_bytes()
+        .to_vec();
+
+    let fields = encode_header_fields(&block)?;
+
+    let mut stream = rlp::RlpStream::new_list(fields.len());
+    for field in &fields
+    {
+        stream.append_raw(field, 1);
+    }
+    let header = stream.out().to_vec();
+
+    if keccak256(&header).to_vec() != block_hash
+    {
+        return Err("Encoded header does not hash to the block's reported hash".into());
+    }
+
+    let payload_len: usize = fields.iter().map(|f| f.len()).sum();
+    let prefix_len = header.len() - payload_len;
+    let state_root_offset = prefix_len
+        + fields[..STATE_ROOT_FIELD_INDEX]
+            .iter()
+            .map(|f| f.len())
+            .sum::<usize>();
+
+    let padded_header = right_pad(&header, MAX_HEADER_LENGTH)?;
+
+    Ok((
+        block_hash,
+        BlockHeaderProof {
+            header: padded_header,
+            state_root_offset,
+        },
+    ))
+}
@@ -1,6 +1,15 @@
 use ethers::prelude::*;
 use ethers::utils::rlp;
 
+mod account;
+mod header;
+mod mpt;
+mod trie;
+pub use account::{decode_account_state, AccountState};
+pub use header::{fetch_block_header_proof, BlockHeaderProof};
+pub use mpt::verify_proof;
+pub use trie::{build_local_proof, KvEntry};
+
 /// Maximum length of a state or storage trie node in bytes
 const MAX_TRIE_NODE_LENGTH: usize = 532;
 
@@ -37,9 +46,30 @@ impl TrieProof
             proof_name, self.key, self.proof, self.depth, self.value
         )
     }
+
+    /// Proof Toml array formatter. Returns a string with a TOML array of tables, one per proof,
+    /// suitable for verifying several keys against a single shared root in one Noir program.
+    ///
+    /// # Arguments
+    /// * `proofs` - Trie proofs to format, e.g. several storage proofs sharing a storage root
+    /// * `proof_name` - Name of the TOML array the proofs are collected under
+    pub fn to_toml_array_string(proofs: &[TrieProof], proof_name: &str) -> String
+    {
+        proofs
+            .iter()
+            .map(|p| {
+                format!(
+                    "[[{}]]\nkey = {:#04x?}\nproof = {:#04x?}\ndepth = {:#04x?}\nvalue = {:#04x?}",
+                    proof_name, p.key, p.proof, p.depth, p.value
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
 }
 
-/// State proof fetcher and preprocessor. Returns a pair consisting of the state root as a byte vector and the preprocessed state proof.
+/// State proof fetcher and preprocessor. Returns a triple consisting of the state root as a byte vector, the
+/// preprocessed state proof, and the decomposed account state the proof resolves to.
 ///
 /// # Arguments
 /// * `provider` - Provider for interacting with Ethereum JSON RPC API
@@ -51,7 +81,7 @@ pub async fn fetch_state_proof<T: JsonRpcClient>(
     block_number: U64,
     address: Address,
     max_depth: usize,
-) -> Result<(Vec<u8>, TrieProof), Box<dyn std::error::Error>>
+) -> Result<(Vec<u8>, TrieProof, AccountState), Box<dyn std::error::Error>>
 {
     // Call eth_getProof
     let eip1186pr = provider
@@ -79,6 +109,13 @@ pub async fn fetch_state_proof<T: JsonRpcClient>(
         .ok_or("RLP list empty")?
         .to_vec();
 
+    // Verify the proof locally before trusting it: a malformed or stale proof should be caught
+    // here rather than surfacing as an opaque failure inside the Noir circuit.
+    verify_proof(&state_root, address.as_bytes(), &state_proof, &value)?;
+
+    // Decode the account state out of the terminal value before it is padded for Noir
+    let account_state = decode_account_state(&value)?;
+
     // Preprocess state proof
     let preproc_proof = preprocess_proof(
         state_proof.clone(),
@@ -89,54 +126,170 @@ pub async fn fetch_state_proof<T: JsonRpcClient>(
         MAX_ACCOUNT_STATE_LENGTH,
     )?;
 
-    Ok((state_root, preproc_proof))
+    Ok((state_root, preproc_proof, account_state))
 }
 
-/// Storage proof fetcher and preprocessor. Returns a pair consisting of the storage root as a byte vector the preprocessed storage root.
+/// Storage proof fetcher and preprocessor. Returns a pair consisting of the storage root as a byte vector and the
+/// preprocessed storage proof for each key, in the order the keys were given. All proofs are fetched in a single
+/// `eth_getProof` call and share the one storage root.
 ///
 /// # Arguments
 /// * `provider` - Provider for interacting with Ethereum JSON RPC API
-/// * `block_number` - Block number with respect to which the storage proof is retrieved
-/// * `address` - Address of the account from which the storage proof is retrieved
-/// * `key` - 32-byte key of the storage slot for which the storage proof is retreieved
-/// * `max_depth` - Maximum admissible depth of the storage proof
+/// * `block_number` - Block number with respect to which the storage proofs are retrieved
+/// * `address` - Address of the account from which the storage proofs are retrieved
+/// * `keys` - 32-byte keys of the storage slots for which storage proofs are retrieved
+/// * `max_depth` - Maximum admissible depth of a storage proof
 pub async fn fetch_storage_proof<T: JsonRpcClient>(
     provider: Provider<T>,
     block_number: U64,
-    key: H256,
+    keys: Vec<H256>,
     address: Address,
     max_depth: usize,
-) -> Result<(Vec<u8>, TrieProof), Box<dyn std::error::Error>>
+) -> Result<(Vec<u8>, Vec<TrieProof>), Box<dyn std::error::Error>>
 {
-    // Call eth_getProof
+    // Call eth_getProof with all keys at once
+    let eip1186pr = provider
+        .get_proof(address, keys, Some(BlockId::from(block_number)))
+        .await?;
+
+    // ...and storage root, shared by every storage proof returned
+    let storage_root = eip1186pr.storage_hash.as_bytes().to_vec();
+
+    // Pick out and preprocess each storage proof
+    let mut preproc_proofs = Vec::with_capacity(eip1186pr.storage_proof.len());
+    for storage_proof in eip1186pr.storage_proof
+    {
+        // Extract value as big endian byte array
+        let mut value = [0; 32];
+        storage_proof.value.to_big_endian(&mut value);
+
+        // Verify the proof locally before trusting it: a malformed or stale proof should be caught
+        // here rather than surfacing as an opaque failure inside the Noir circuit. The trie stores
+        // the value in its minimal (unpadded) RLP encoding, so leading zero bytes must be stripped
+        // and the result RLP-encoded before comparison.
+        let trimmed_value: Vec<u8> = {
+            let first_nonzero = value.iter().position(|&b| b != 0).unwrap_or(value.len());
+            value[first_nonzero..].to_vec()
+        };
+        verify_proof(
+            &storage_root,
+            storage_proof.key.as_bytes(),
+            &storage_proof.proof,
+            &rlp::encode(&trimmed_value),
+        )?;
+
+        preproc_proofs.push(preprocess_proof(
+            storage_proof.proof,
+            storage_proof.key.as_bytes().to_vec(),
+            value.to_vec(),
+            max_depth,
+            MAX_TRIE_NODE_LENGTH,
+            MAX_STORAGE_VALUE_LENGTH,
+        )?);
+    }
+
+    Ok((storage_root, preproc_proofs))
+}
+
+/// Combined account and storage proof fetcher. Fetches the account proof and a storage proof for
+/// `key` in a single `eth_getProof` call, then links them by verifying that the account's decoded
+/// `storage_hash` field equals the root the storage proof resolves against, so that the storage
+/// root does not have to be trusted out of band. Returns the state root, the preprocessed state
+/// proof, the decoded account state, and the preprocessed storage proof for `key`.
+///
+/// # Arguments
+/// * `provider` - Provider for interacting with Ethereum JSON RPC API
+/// * `block_number` - Block number with respect to which the proofs are retrieved
+/// * `address` - Address of the account whose storage slot is being proven
+/// * `key` - 32-byte key of the storage slot for which a storage proof is retrieved
+/// * `max_depth` - Maximum admissible depth of either proof
+pub async fn fetch_account_storage_proof<T: JsonRpcClient>(
+    provider: Provider<T>,
+    block_number: U64,
+    address: Address,
+    key: H256,
+    max_depth: usize,
+) -> Result<(Vec<u8>, TrieProof, AccountState, TrieProof), Box<dyn std::error::Error>>
+{
+    // Call eth_getProof once for both the account proof and the storage proof
     let eip1186pr = provider
         .get_proof(address, vec![key], Some(BlockId::from(block_number)))
         .await?;
 
-    // Pick out storage proof
+    let state_proof = eip1186pr.account_proof;
+    let storage_root = eip1186pr.storage_hash.as_bytes().to_vec();
     let storage_proof = eip1186pr
         .storage_proof
-        .get(0)
+        .into_iter()
+        .next()
         .ok_or("No storage proof returned")?;
 
-    // ...and storage root
-    let storage_root = eip1186pr.storage_hash.as_bytes().to_vec();
+    // ...and state root, for which we need to fetch the current block
+    let block: Block<H256> = provider
+        .get_block(block_number)
+        .await?
+        .ok_or(format!("Could not fetch block number {}", block_number))?;
+    let state_root = block.state_root.as_bytes().to_vec();
+
+    // Extract the account state value from the terminal state proof node
+    let value = rlp::Rlp::new(
+        state_proof
+            .last() // Terminal proof node
+            .ok_or("State proof empty")?,
+    ) // Proof should have been non-empty
+        .as_list::<Vec<u8>>()?
+        .last() // Extract value
+        .ok_or("RLP list empty")?
+        .to_vec();
 
-    // Extract value as big endian byte array
-    let mut value = [0; 32];
-    storage_proof.value.to_big_endian(&mut value);
+    // Verify the state proof locally before trusting it: a malformed or stale proof should be
+    // caught here rather than surfacing as an opaque failure inside the Noir circuit.
+    verify_proof(&state_root, address.as_bytes(), &state_proof, &value)?;
+    let account_state = decode_account_state(&value)?;
 
-    // Preprocess storage proof
-    let preproc_proof = preprocess_proof(
-        storage_proof.clone().proof,
-        key.as_bytes().to_vec(),
-        value.to_vec(),
+    // Link the account's own view of the storage root to the root the storage proof resolves
+    // against, closing the trust gap between the two proofs.
+    if storage_root != account_state.storage_hash
+    {
+        return Err("Account's storageHash does not match the fetched storage root".into());
+    }
+
+    let preproc_state_proof = preprocess_proof(
+        state_proof,
+        address.as_bytes().to_vec(),
+        value,
+        max_depth,
+        MAX_TRIE_NODE_LENGTH,
+        MAX_ACCOUNT_STATE_LENGTH,
+    )?;
+
+    // Extract storage value as big endian byte array
+    let mut storage_value = [0; 32];
+    storage_proof.value.to_big_endian(&mut storage_value);
+
+    // The trie stores the value in its minimal (unpadded) RLP encoding, so leading zero bytes
+    // must be stripped and the result RLP-encoded before comparison.
+    let trimmed_storage_value: Vec<u8> = {
+        let first_nonzero = storage_value.iter().position(|&b| b != 0).unwrap_or(storage_value.len());
+        storage_value[first_nonzero..].to_vec()
+    };
+    verify_proof(
+        &storage_root,
+        storage_proof.key.as_bytes(),
+        &storage_proof.proof,
+        &rlp::encode(&trimmed_storage_value),
+    )?;
+
+    let preproc_storage_proof = preprocess_proof(
+        storage_proof.proof,
+        storage_proof.key.as_bytes().to_vec(),
+        storage_value.to_vec(),
         max_depth,
         MAX_TRIE_NODE_LENGTH,
         MAX_STORAGE_VALUE_LENGTH,
     )?;
 
-    Ok((storage_root, preproc_proof))
+    Ok((state_root, preproc_state_proof, account_state, preproc_storage_proof))
 }
 
 /// Trie proof preprocessor. Returns a proof suitable for use in a Noir program using the noir-trie-proofs library.
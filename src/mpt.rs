@@ -0,0 +1,278 @@
+use ethers::types::Bytes;
+use ethers::utils::keccak256;
+use ethers::utils::rlp;
+
+/// Splits a byte slice into its constituent nibbles (big endian, high nibble first).
+pub(crate) fn to_nibbles(bytes: &[u8]) -> Vec<u8>
+{
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+/// Decodes a hex-prefix (compact) encoded path as found in the first item of an extension or
+/// leaf node. Returns a pair consisting of a flag indicating whether the node is a leaf and the
+/// decoded nibbles of the path.
+///
+/// # Arguments
+/// * `path` - Hex-prefix encoded path, i.e. the first item of a 2-item trie node
+fn hex_prefix_decode(path: &[u8]) -> Result<(bool, Vec<u8>), Box<dyn std::error::Error>>
+{
+    let first = *path.first().ok_or("Hex-prefix encoded path is empty")?;
+
+    let prefix = first >> 4;
+    let is_leaf = prefix == 2 || prefix == 3;
+    let is_odd = prefix == 1 || prefix == 3;
+
+    let mut nibbles = Vec::new();
+    if is_odd
+    {
+        nibbles.push(first & 0x0f);
+    }
+    for b in &path[1..]
+    {
+        nibbles.push(b >> 4);
+        nibbles.push(b & 0x0f);
+    }
+
+    Ok((is_leaf, nibbles))
+}
+
+/// Resolves a branch or extension node's child reference to the child node's own RLP bytes.
+///
+/// A child whose RLP encoding is shorter than 32 bytes is embedded directly in its parent as a
+/// nested list, exactly as `trie::encode_node` embeds it when building a proof offline; in that
+/// case its bytes are already in hand and `proof` contributes no separate entry for it. Otherwise
+/// the child is referenced by its 32-byte keccak256 hash, and the next as-yet-unconsumed node in
+/// `proof` is taken as the child, its hash checked against the reference. Returns the child's own
+/// RLP bytes in either case, or an empty vector if the slot was empty.
+///
+/// # Arguments
+/// * `item` - RLP item occupying the child's slot in the parent node
+/// * `proof` - Full list of proof nodes, in root-to-leaf order
+/// * `proof_idx` - Index of the next as-yet-unconsumed node in `proof`; advanced past a node this
+///   call consumes
+fn resolve_child(
+    item: rlp::Rlp,
+    proof: &[Bytes],
+    proof_idx: &mut usize,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>>
+{
+    if item.is_list()
+    {
+        return Ok(item.as_raw().to_vec());
+    }
+
+    let hash: Vec<u8> = item.as_val()?;
+    if hash.is_empty()
+    {
+        return Ok(hash);
+    }
+
+    let next = proof
+        .get(*proof_idx)
+        .ok_or("Proof ended before the value could be resolved")?;
+    if keccak256(next.as_ref()).to_vec() != hash
+    {
+        return Err(format!("Hash mismatch at proof node {}", proof_idx).into());
+    }
+    *proof_idx += 1;
+
+    Ok(next.to_vec())
+}
+
+/// Verifies a Merkle-Patricia trie proof locally, i.e. without relying on the Noir circuit to
+/// catch a malformed proof. Returns `Ok(())` if `proof` resolves `key` to `value` under `root`,
+/// and an error describing the first inconsistency found otherwise.
+///
+/// # Arguments
+/// * `root` - Expected 32-byte root hash of the trie
+/// * `key` - Unhashed key (account address or storage slot) the proof resolves
+/// * `proof` - Nodes genuinely referenced by hash, in root-to-leaf order, exactly as returned by
+///   `eth_getProof`; a node embedded directly in its parent (RLP-encoded under 32 bytes)
+///   contributes no separate entry here, mirroring how go-ethereum's `trie.Prove` only records a
+///   proof-db entry for a node's collapsed form when that form is a hash (or it is the root)
+/// * `value` - Expected RLP-decoded value the key should resolve to
+pub fn verify_proof(
+    root: &[u8],
+    key: &[u8],
+    proof: &[Bytes],
+    value: &[u8],
+) -> Result<(), Box<dyn std::error::Error>>
+{
+    let nibbles = to_nibbles(&keccak256(key));
+    let mut nibble_idx = 0;
+
+    // The root is always hashed regardless of its encoded size, so it always occupies proof[0].
+    let mut proof_idx = 0;
+    let mut current: Vec<u8> = proof.get(proof_idx).ok_or("Proof is empty")?.to_vec();
+    if keccak256(&current).to_vec() != root
+    {
+        return Err("Hash mismatch at the root".into());
+    }
+    proof_idx += 1;
+
+    loop
+    {
+        let rlp = rlp::Rlp::new(&current);
+        match rlp.item_count()?
+        {
+            17 =>
+            {
+                // Branch node: either resolve the value (key exhausted) or follow the nibble
+                if nibble_idx == nibbles.len()
+                {
+                    let v: Vec<u8> = rlp.at(16)?.as_val()?;
+                    return if v == value
+                    {
+                        Ok(())
+                    } else {
+                        Err("Resolved value does not match the expected value".into())
+                    };
+                }
+
+                let child = resolve_child(rlp.at(nibbles[nibble_idx] as usize)?, proof, &mut proof_idx)?;
+                if child.is_empty()
+                {
+                    return Err("Key is absent from the trie (empty branch slot)".into());
+                }
+                current = child;
+                nibble_idx += 1;
+            }
+            2 =>
+            {
+                // Extension or leaf node, distinguished by the hex-prefix encoding of item 0
+                let path: Vec<u8> = rlp.at(0)?.as_val()?;
+                let (is_leaf, path_nibbles) = hex_prefix_decode(&path)?;
+
+                if nibbles[nibble_idx..].len() < path_nibbles.len()
+                    || nibbles[nibble_idx..nibble_idx + path_nibbles.len()] != path_nibbles[..]
+                {
+                    return Err("Path mismatch against a leaf or extension node".into());
+                }
+                nibble_idx += path_nibbles.len();
+
+                if is_leaf
+                {
+                    if nibble_idx != nibbles.len()
+                    {
+                        return Err("Leaf node reached before the key was fully consumed".into());
+                    }
+                    let v: Vec<u8> = rlp.at(1)?.as_val()?;
+                    return if v == value
+                    {
+                        Ok(())
+                    } else {
+                        Err("Resolved value does not match the expected value".into())
+                    };
+                }
+                current = resolve_child(rlp.at(1)?, proof, &mut proof_idx)?;
+            }
+            n => return Err(format!("Unexpected node with {} items", n).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use ethers::utils::rlp::RlpStream;
+
+    /// RLP-encodes a 2-item leaf node from raw path nibbles and a value, without going through
+    /// `trie::encode_node`, so `verify_proof` can be driven directly against hand-built nodes.
+    fn leaf(path_nibbles: &[u8], value: &[u8]) -> Vec<u8>
+    {
+        let is_odd = path_nibbles.len() % 2 == 1;
+        let mut encoded_path = vec![if is_odd { 0x30 | path_nibbles[0] } else { 0x20 }];
+        let rest = if is_odd { &path_nibbles[1..] } else { path_nibbles };
+        for pair in rest.chunks(2)
+        {
+            encoded_path.push((pair[0] << 4) | pair.get(1).copied().unwrap_or(0));
+        }
+
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&encoded_path);
+        stream.append(&value.to_vec());
+        stream.out().to_vec()
+    }
+
+    #[test]
+    fn rejects_a_root_hash_mismatch()
+    {
+        let node = leaf(&[0; 64], b"value");
+        let wrong_root = keccak256(b"not the real root").to_vec();
+
+        let err = verify_proof(&wrong_root, b"key", &[Bytes::from(node)], b"value").unwrap_err();
+        assert!(err.to_string().contains("root"));
+    }
+
+    #[test]
+    fn rejects_an_absent_key_via_an_empty_branch_slot()
+    {
+        // A branch with every slot and the value empty: no matter which nibble the key hashes to
+        // first, that slot is empty.
+        let mut stream = RlpStream::new_list(17);
+        for _ in 0..17
+        {
+            stream.append_empty_data();
+        }
+        let branch = stream.out().to_vec();
+        let root = keccak256(&branch).to_vec();
+
+        let err = verify_proof(&root, b"key", &[Bytes::from(branch)], b"value").unwrap_err();
+        assert!(err.to_string().contains("absent"));
+    }
+
+    #[test]
+    fn rejects_a_path_mismatch_against_a_leaf()
+    {
+        // A leaf whose encoded path cannot possibly match keccak256("key")'s nibbles.
+        let node = leaf(&[0xf; 64], b"value");
+        let root = keccak256(&node).to_vec();
+
+        let err = verify_proof(&root, b"key", &[Bytes::from(node)], b"value").unwrap_err();
+        assert!(err.to_string().contains("Path mismatch"));
+    }
+
+    #[test]
+    fn resolves_a_value_behind_an_embedded_branch_child()
+    {
+        // A branch whose single non-empty slot embeds a short leaf node directly (the leaf's own
+        // RLP encoding is under 32 bytes), exactly as a real `eth_getProof` response would omit a
+        // separate proof entry for it. A leaf's path is only that short deep in the trie, so the
+        // branch sits behind a root extension covering all but the last two key nibbles, and is
+        // itself short enough to be embedded in that extension.
+        let key: &[u8] = b"embedded-child-key";
+        let nibbles = to_nibbles(&keccak256(key));
+        let value = b"v".to_vec();
+
+        let embedded_leaf = leaf(&nibbles[63..], &value);
+        assert!(embedded_leaf.len() < 32, "fixture must actually embed");
+
+        let mut stream = RlpStream::new_list(17);
+        for i in 0..16
+        {
+            if i == nibbles[62] as usize
+            {
+                stream.append_raw(&embedded_leaf, 1);
+            } else {
+                stream.append_empty_data();
+            }
+        }
+        stream.append_empty_data();
+        let branch = stream.out().to_vec();
+        assert!(branch.len() < 32, "fixture must actually embed");
+
+        // Even-length extension path: prefix byte 0x00, then the nibbles packed in pairs
+        let mut extension_path = vec![0x00];
+        extension_path.extend(nibbles[..62].chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&extension_path);
+        stream.append_raw(&branch, 1);
+        let extension = stream.out().to_vec();
+        let root = keccak256(&extension).to_vec();
+
+        // Only the root extension is a genuine proof entry; the embedded branch and leaf contribute
+        // none.
+        verify_proof(&root, key, &[Bytes::from(extension)], &value).unwrap();
+    }
+}
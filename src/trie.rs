@@ -0,0 +1,432 @@
+use ethers::types::Bytes;
+use ethers::utils::keccak256;
+use ethers::utils::rlp;
+use serde::Deserialize;
+
+use crate::mpt::to_nibbles;
+use crate::{preprocess_proof, verify_proof, TrieProof, MAX_TRIE_NODE_LENGTH};
+
+/// A single entry of a key/value trie fixture, as read from a `--kv-file`.
+#[derive(Deserialize)]
+pub struct KvEntry
+{
+    /// Unhashed key, as a `0x`-prefixed hex string
+    pub key: Bytes,
+    /// Value the key resolves to, as a `0x`-prefixed hex string
+    pub value: Bytes,
+}
+
+/// In-memory node of a secure Merkle-Patricia trie being built. Unlike the final, committed trie,
+/// children are kept as plain Rust values rather than hash references so that the trie can be
+/// grown incrementally by `insert`.
+enum Node
+{
+    Empty,
+    Leaf
+    {
+        /// Remaining key nibbles from this node to the leaf
+        path: Vec<u8>,
+        value: Vec<u8>,
+    },
+    Extension
+    {
+        /// Shared key nibbles from this node to its (unique) child
+        path: Vec<u8>,
+        child: Box<Node>,
+    },
+    Branch
+    {
+        children: [Box<Node>; 16],
+        value: Option<Vec<u8>>,
+    },
+}
+
+impl Node
+{
+    fn empty_branch() -> Node
+    {
+        Node::Branch {
+            children: std::array::from_fn(|_| Box::new(Node::Empty)),
+            value: None,
+        }
+    }
+}
+
+/// Length of the common prefix shared by two nibble slices.
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize
+{
+    a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count()
+}
+
+/// Wraps `node` in an `Extension` sharing `path`, unless `path` is empty, in which case `node` is
+/// returned unchanged.
+fn wrap_with_extension(path: &[u8], node: Node) -> Node
+{
+    if path.is_empty()
+    {
+        node
+    } else {
+        Node::Extension {
+            path: path.to_vec(),
+            child: Box::new(node),
+        }
+    }
+}
+
+/// Inserts `value` at `nibbles` into `node`, returning the updated (sub)trie.
+fn insert(node: Node, nibbles: &[u8], value: Vec<u8>) -> Node
+{
+    match node
+    {
+        Node::Empty => Node::Leaf {
+            path: nibbles.to_vec(),
+            value,
+        },
+        Node::Leaf { path, value: leaf_value } =>
+        {
+            let common = common_prefix_len(&path, nibbles);
+            if common == path.len() && common == nibbles.len()
+            {
+                return Node::Leaf { path, value };
+            }
+
+            let mut branch = Node::empty_branch();
+            if let Node::Branch {
+                ref mut children,
+                value: ref mut branch_value,
+            } = branch
+            {
+                if common == path.len()
+                {
+                    *branch_value = Some(leaf_value);
+                } else {
+                    children[path[common] as usize] = Box::new(Node::Leaf {
+                        path: path[common + 1..].to_vec(),
+                        value: leaf_value,
+                    });
+                }
+
+                if common == nibbles.len()
+                {
+                    *branch_value = Some(value);
+                } else {
+                    children[nibbles[common] as usize] = Box::new(Node::Leaf {
+                        path: nibbles[common + 1..].to_vec(),
+                        value,
+                    });
+                }
+            }
+
+            wrap_with_extension(&nibbles[..common], branch)
+        }
+        Node::Extension { path, child } =>
+        {
+            let common = common_prefix_len(&path, nibbles);
+            if common == path.len()
+            {
+                return wrap_with_extension(&path, insert(*child, &nibbles[common..], value));
+            }
+
+            let mut branch = Node::empty_branch();
+            if let Node::Branch {
+                ref mut children,
+                value: ref mut branch_value,
+            } = branch
+            {
+                let ext_remainder = path[common + 1..].to_vec();
+                children[path[common] as usize] = Box::new(wrap_with_extension(&ext_remainder, *child));
+
+                if common == nibbles.len()
+                {
+                    *branch_value = Some(value);
+                } else {
+                    children[nibbles[common] as usize] = Box::new(Node::Leaf {
+                        path: nibbles[common + 1..].to_vec(),
+                        value,
+                    });
+                }
+            }
+
+            wrap_with_extension(&nibbles[..common], branch)
+        }
+        Node::Branch { mut children, value: branch_value } =>
+        {
+            if nibbles.is_empty()
+            {
+                Node::Branch {
+                    children,
+                    value: Some(value),
+                }
+            } else {
+                let idx = nibbles[0] as usize;
+                let existing = std::mem::replace(&mut children[idx], Box::new(Node::Empty));
+                children[idx] = Box::new(insert(*existing, &nibbles[1..], value));
+                Node::Branch {
+                    children,
+                    value: branch_value,
+                }
+            }
+        }
+    }
+}
+
+/// Hex-prefix (compact) encodes a nibble path, as found in the first item of an extension or leaf
+/// node.
+///
+/// # Arguments
+/// * `nibbles` - Path nibbles to encode
+/// * `is_leaf` - Whether the encoded path terminates in a leaf
+fn hex_prefix_encode(nibbles: &[u8], is_leaf: bool) -> Vec<u8>
+{
+    let is_odd = nibbles.len() % 2 == 1;
+    let prefix_nibble = 2 * (is_leaf as u8) + (is_odd as u8);
+
+    let mut out = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let mut rest = nibbles;
+    if is_odd
+    {
+        out.push((prefix_nibble << 4) | nibbles[0]);
+        rest = &nibbles[1..];
+    } else {
+        out.push(prefix_nibble << 4);
+    }
+    for pair in rest.chunks(2)
+    {
+        out.push((pair[0] << 4) | pair[1]);
+    }
+
+    out
+}
+
+/// RLP-encodes a trie node, recursively embedding or hash-referencing its children as dictated by
+/// their own encoded length (children shorter than 32 bytes are embedded directly).
+fn encode_node(node: &Node) -> Vec<u8>
+{
+    match node
+    {
+        Node::Empty => vec![0x80], // RLP encoding of the empty byte string
+        Node::Leaf { path, value } =>
+        {
+            let mut stream = rlp::RlpStream::new_list(2);
+            stream.append(&hex_prefix_encode(path, true));
+            stream.append(value);
+            stream.out().to_vec()
+        }
+        Node::Extension { path, child } =>
+        {
+            let child_rlp = encode_node(child);
+            let mut stream = rlp::RlpStream::new_list(2);
+            stream.append(&hex_prefix_encode(path, false));
+            if child_rlp.len() < 32
+            {
+                stream.append_raw(&child_rlp, 1);
+            } else {
+                stream.append(&keccak256(&child_rlp).to_vec());
+            }
+            stream.out().to_vec()
+        }
+        Node::Branch { children, value } =>
+        {
+            let mut stream = rlp::RlpStream::new_list(17);
+            for child in children
+            {
+                match child.as_ref()
+                {
+                    Node::Empty => {
+                        stream.append_empty_data();
+                    }
+                    other =>
+                    {
+                        let child_rlp = encode_node(other);
+                        if child_rlp.len() < 32
+                        {
+                            stream.append_raw(&child_rlp, 1);
+                        } else {
+                            stream.append(&keccak256(&child_rlp).to_vec());
+                        }
+                    }
+                }
+            }
+            match value
+            {
+                Some(v) => {
+                    stream.append(v);
+                }
+                None => {
+                    stream.append_empty_data();
+                }
+            }
+            stream.out().to_vec()
+        }
+    }
+}
+
+/// Walks `node` towards `nibbles`, recording the RLP encoding of every node visited into `proof`,
+/// mirroring a trie "recorder". A node's own encoding is only recorded as a separate proof entry
+/// when it is genuinely referenced by hash from its parent, i.e. when it is the root (`is_root`)
+/// or its own RLP encoding is 32 bytes or longer; a shorter node is embedded directly in its
+/// parent's encoding (see `encode_node`) and so contributes no entry of its own, matching
+/// go-ethereum's `trie.Prove`, which only records a proof-db entry for a node's collapsed form
+/// when that form is a hash (or it is the root). Returns the resolved value.
+fn walk_record(
+    node: &Node,
+    nibbles: &[u8],
+    proof: &mut Vec<Vec<u8>>,
+    is_root: bool,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>>
+{
+    let node_rlp = encode_node(node);
+    if is_root || node_rlp.len() >= 32
+    {
+        proof.push(node_rlp);
+    }
+
+    match node
+    {
+        Node::Empty => Err("Key is absent from the trie".into()),
+        Node::Leaf { path, value } =>
+        {
+            if path.as_slice() == nibbles
+            {
+                Ok(value.clone())
+            } else {
+                Err("Key is absent from the trie".into())
+            }
+        }
+        Node::Extension { path, child } =>
+        {
+            if nibbles.len() < path.len() || nibbles[..path.len()] != path[..]
+            {
+                return Err("Key is absent from the trie".into());
+            }
+            walk_record(child, &nibbles[path.len()..], proof, false)
+        }
+        Node::Branch { children, value } =>
+        {
+            if nibbles.is_empty()
+            {
+                value.clone().ok_or_else(|| "Key is absent from the trie".into())
+            } else {
+                walk_record(&children[nibbles[0] as usize], &nibbles[1..], proof, false)
+            }
+        }
+    }
+}
+
+/// Builds a secure Merkle-Patricia trie in memory from `entries` (keys are hashed with keccak256,
+/// exactly as the Ethereum state and storage tries do) and produces a `TrieProof` for `key`,
+/// mirroring the shape of a proof fetched over RPC. Returns the computed root hash together with
+/// the preprocessed proof.
+///
+/// # Arguments
+/// * `entries` - Key/value pairs to insert into the trie
+/// * `key` - Key to produce a trie proof for; must be present in `entries`
+/// * `max_depth` - Maximum admissible depth of the proof
+pub fn build_local_proof(
+    entries: &[(Vec<u8>, Vec<u8>)],
+    key: &[u8],
+    max_depth: usize,
+) -> Result<(Vec<u8>, TrieProof), Box<dyn std::error::Error>>
+{
+    let mut root = Node::Empty;
+    for (k, v) in entries
+    {
+        root = insert(root, &to_nibbles(&keccak256(k)), v.clone());
+    }
+
+    let mut proof_nodes = Vec::new();
+    let value = walk_record(&root, &to_nibbles(&keccak256(key)), &mut proof_nodes, true)?;
+    // The root is always recorded regardless of its own encoded length, so it is always first.
+    let root_hash = keccak256(&proof_nodes[0]).to_vec();
+    let proof: Vec<Bytes> = proof_nodes.into_iter().map(Bytes::from).collect();
+
+    verify_proof(&root_hash, key, &proof, &value)?;
+
+    let max_value_len = value.len();
+    let preproc_proof = preprocess_proof(
+        proof,
+        key.to_vec(),
+        value,
+        max_depth,
+        MAX_TRIE_NODE_LENGTH,
+        max_value_len,
+    )?;
+
+    Ok((root_hash, preproc_proof))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    /// A handful of distinct keys/values. Keys are hashed with keccak256, so their paths are long
+    /// enough that every node along a proof path is referenced by hash rather than embedded;
+    /// embedded nodes are covered by `walk_record_skips_proof_entries_for_embedded_nodes`.
+    fn fixture() -> Vec<(Vec<u8>, Vec<u8>)>
+    {
+        vec![
+            (b"alpha".to_vec(), vec![0x01]),
+            (b"beta".to_vec(), vec![0x02, 0x02]),
+            (b"gamma".to_vec(), vec![0x03, 0x03, 0x03]),
+            (b"delta".to_vec(), vec![0x04, 0x04, 0x04, 0x04]),
+        ]
+    }
+
+    #[test]
+    fn build_local_proof_round_trips_for_every_key()
+    {
+        let entries = fixture();
+        for (key, value) in &entries
+        {
+            let (root, proof) = build_local_proof(&entries, key, 8)
+                .expect("a proof for an entry that is actually in the trie should build and verify");
+            assert_eq!(root.len(), 32);
+
+            let raw_proof: Vec<Bytes> = proof
+                .proof
+                .chunks(MAX_TRIE_NODE_LENGTH)
+                .take(proof.depth)
+                .map(|chunk| {
+                    // Each node is zero-padded up to MAX_TRIE_NODE_LENGTH; trim the padding back
+                    // off before handing the node back to verify_proof.
+                    let info = rlp::Rlp::new(chunk).payload_info().unwrap();
+                    let unpadded_len = info.header_len + info.value_len;
+                    Bytes::from(chunk[..unpadded_len].to_vec())
+                })
+                .collect();
+
+            verify_proof(&root, key, &raw_proof, value)
+                .expect("re-verifying the unpadded proof nodes independently should also succeed");
+        }
+    }
+
+    #[test]
+    fn build_local_proof_rejects_a_key_absent_from_the_trie()
+    {
+        let entries = fixture();
+        assert!(build_local_proof(&entries, b"not-a-key", 8).is_err());
+    }
+
+    #[test]
+    fn walk_record_skips_proof_entries_for_embedded_nodes()
+    {
+        // Two keys sharing a single leading nibble, each with a short remaining path and a tiny
+        // value, so the leaves (and the branch holding them) are well under 32 bytes once
+        // RLP-encoded and so are embedded directly rather than referenced by hash. Built from raw
+        // nibbles, bypassing the keccak256 hashing `build_local_proof` applies, so the embedding is
+        // guaranteed rather than incidental.
+        let mut root = Node::Empty;
+        root = insert(root, &[0x1, 0x2], vec![0x01]);
+        root = insert(root, &[0x1, 0x3], vec![0x02]);
+
+        let mut proof = Vec::new();
+        let value = walk_record(&root, &[0x1, 0x2], &mut proof, true).unwrap();
+        assert_eq!(value, vec![0x01]);
+
+        // Only the root is recorded: the branch and its leaves all collapse into the root's own
+        // encoding once embedded, exactly as a real `eth_getProof` response would omit a separate
+        // proof entry for them.
+        assert_eq!(proof.len(), 1);
+    }
+}